@@ -2,14 +2,22 @@ use std::{
     borrow::Cow,
     cmp::Ordering,
     collections::{HashMap, HashSet},
-    fs::{self, File},
+    fs::{self, File, OpenOptions},
     io,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc, Mutex,
+    },
+    thread,
     time::SystemTime,
 };
+#[cfg(unix)]
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
 
 use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum, ValueHint};
+use filetime::FileTime;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -24,7 +32,18 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-#[derive(Clone, Copy, ValueEnum, PartialEq)]
+#[derive(Clone, Copy, Default, ValueEnum, PartialEq)]
+enum BackupMode {
+    #[default]
+    #[value(help = "never make backups")]
+    None,
+    #[value(help = "always make simple backups, appending the suffix")]
+    Simple,
+    #[value(help = "make numbered backups")]
+    Numbered,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq)]
 enum OverwriteMode {
     #[value(help = "always overwrite")]
     Always,
@@ -32,10 +51,31 @@ enum OverwriteMode {
     FastComp,
     #[value(help = "overwrite when hashsum of the source and the destination are different")]
     DeepComp,
+    #[value(
+        help = "overwrite when the sizes differ or the source's mtime is newer than the \
+                destination's, falling back to a hash comparison for sources modified within \
+                the second this run started"
+    )]
+    Mtime,
     #[value(help = "never overwrite")]
     Never,
 }
 
+#[derive(Clone, Copy, Default, ValueEnum, PartialEq)]
+enum HashAlgo {
+    #[default]
+    #[value(help = "SHA-1, slower but ubiquitous")]
+    Sha1,
+    #[value(help = "BLAKE3, much faster on large files")]
+    Blake3,
+}
+
+#[derive(PartialEq, Eq)]
+enum Hash {
+    Sha1(Vec<u8>),
+    Blake3(blake3::Hash),
+}
+
 struct Term {
     term:     console::Term,
     progress: Option<ProgressBar>,
@@ -93,6 +133,10 @@ impl Term {
             progress.finish_and_clear();
         }
     }
+
+    fn progress_handle(&self) -> Option<ProgressBar> {
+        self.progress.clone()
+    }
 }
 
 impl Default for Term {
@@ -133,13 +177,86 @@ struct Task {
         default_value_t = false
     )]
     dry_run:        bool,
+    #[arg(
+        short,
+        long = "jobs",
+        value_name = "N",
+        help = "Number of worker threads used for copying, removing and hashing files",
+        default_value_t = 4
+    )]
+    jobs:           usize,
+    #[arg(
+        long = "backup",
+        value_enum,
+        value_name = "MODE",
+        help = "Make a backup of each existing destination file before overwriting or removing \
+                it",
+        num_args = 0..=1,
+        default_missing_value = "simple",
+        default_value_t = BackupMode::None,
+    )]
+    backup:         BackupMode,
+    #[arg(
+        long = "suffix",
+        value_name = "SUFFIX",
+        help = "Backup suffix used in simple backup mode",
+        default_value = "~"
+    )]
+    suffix:         String,
+    #[arg(
+        long = "trash",
+        help = "Move removed files to the system trash instead of deleting them permanently",
+        default_value_t = false
+    )]
+    trash:          bool,
+    #[arg(
+        long = "preserve",
+        help = "Preserve mtime and unix permissions/ownership from source to destination when \
+                copying",
+        default_value_t = false
+    )]
+    preserve:       bool,
+    #[arg(
+        value_enum,
+        long = "hash",
+        value_name = "ALGO",
+        help = "Content-hash algorithm used for deep comparison and rename detection",
+        default_value_t = HashAlgo::Sha1,
+    )]
+    hash_algo:      HashAlgo,
 
     #[clap(skip)]
     term: Term,
 }
 
+/// Per-run options threaded through to-do list generation, bundled into one value so that
+/// `generate_to_do_list` doesn't have to take each of them as a separate parameter.
+#[derive(Clone, Copy)]
+struct RunOptions {
+    overwrite_mode: OverwriteMode,
+    scan_start:     SystemTime,
+    hash_algo:      HashAlgo,
+}
+
+/// The outcome of `generate_to_do_list`: which files to add, overwrite, remove, and rename.
+struct ToDoList {
+    add_list:       Vec<PathBuf>,
+    overwrite_list: Vec<PathBuf>,
+    remove_list:    Vec<PathBuf>,
+    rename_list:    Vec<(PathBuf, PathBuf)>,
+}
+
+/// The outcome of `detect_renames`: `add_list`/`remove_list` with renamed entries pulled out
+/// into `rename_list`.
+struct RenameDetection {
+    add_list:    Vec<PathBuf>,
+    remove_list: Vec<PathBuf>,
+    rename_list: Vec<(PathBuf, PathBuf)>,
+}
+
 impl Task {
     fn process(&mut self) -> Result<()> {
+        let scan_start = SystemTime::now();
         let (src, dest) = self.get_src_dest_paths()?;
 
         self.term
@@ -165,29 +282,37 @@ impl Task {
             .context("Failed to collect exist files of destination directory")?;
 
         self.term.act("Generating", "to-do list")?;
-        let (add_list, overwrite_list, remove_list) = self
-            .generate_to_do_list(
-                &src,
-                &dest,
-                &include_files,
-                &dest_files,
-                self.overwrite_mode,
-            )
+        let options = RunOptions {
+            overwrite_mode: self.overwrite_mode,
+            scan_start,
+            hash_algo: self.hash_algo,
+        };
+        let to_do_list = self
+            .generate_to_do_list(&src, &dest, &include_files, &dest_files, options)
             .context("Failed to generate to-do list")?;
 
         if !self.dry_run {
-            self.execute_list("remove", "Removing", &remove_list, remove, &src, &dest)
-                .context("Failed to execute remove list")?;
+            self.execute_rename_list(&to_do_list.rename_list)
+                .context("Failed to execute rename list")?;
+            self.execute_list(
+                "remove",
+                "Removing",
+                &to_do_list.remove_list,
+                remove,
+                &src,
+                &dest,
+            )
+            .context("Failed to execute remove list")?;
             self.execute_list(
                 "overwrite",
                 "Overwriting",
-                &overwrite_list,
+                &to_do_list.overwrite_list,
                 copy,
                 &src,
                 &dest,
             )
             .context("Failed to execute overwrite list")?;
-            self.execute_list("add", "Adding", &add_list, copy, &src, &dest)
+            self.execute_list("add", "Adding", &to_do_list.add_list, copy, &src, &dest)
                 .context("Failed to execute add list")?;
         }
 
@@ -293,11 +418,17 @@ impl Task {
         dest: &Path,
         include_files: &[PathBuf],
         dest_files: &[PathBuf],
-        overwrite_mode: OverwriteMode,
-    ) -> Result<(Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>)> {
+        options: RunOptions,
+    ) -> Result<ToDoList> {
+        let RunOptions {
+            overwrite_mode,
+            scan_start,
+            hash_algo,
+        } = options;
+
         let mut add_list = Vec::new();
-        let mut overwrite_list = Vec::new();
         let mut remove_list = Vec::new();
+        let mut overwrite_candidates = Vec::new();
 
         self.term.new_progress(include_files.len(), "Checking")?;
         for entry in include_files {
@@ -312,26 +443,43 @@ impl Task {
             if !dest_files.contains(entry) {
                 log::debug!("+ {entry_disp}");
                 add_list.push(entry.clone());
-                continue;
+            } else {
+                overwrite_candidates.push(entry.clone());
             }
+            self.term.progress_inc();
+        }
+        self.term.progress_finish();
 
-            let dest = dest.join(entry);
+        self.term
+            .new_progress(overwrite_candidates.len(), "Hashing")?;
+        let progress = self.term.progress_handle();
+        let src_root = src.to_path_buf();
+        let dest_root = dest.to_path_buf();
+        let results = run_in_parallel(self.jobs, overwrite_candidates, progress, move |entry| {
+            let src = src_root.join(&entry);
+            let dest = dest_root.join(&entry);
+            let need = need_overwrite(&src, &dest, overwrite_mode, scan_start, hash_algo)
+                .with_context(|| {
+                    format!(
+                        "Failed to check overwrite: {} -> {}",
+                        src.display(),
+                        dest.display()
+                    )
+                })?;
+            Ok::<_, anyhow::Error>((entry, need))
+        });
+        self.term.progress_finish();
 
-            if need_overwrite(&src, &dest, overwrite_mode).with_context(|| {
-                format!(
-                    "Failed to check overwrite: {} -> {}",
-                    src.display(),
-                    dest.display()
-                )
-            })? {
-                log::debug!("~ {entry_disp}");
+        let mut overwrite_list = Vec::new();
+        for result in results {
+            let (entry, need) = result?;
+            if need {
+                log::debug!("~ {}", entry.display());
             } else {
-                log::debug!("^ {entry_disp}");
-                overwrite_list.push(entry.clone());
+                log::debug!("^ {}", entry.display());
+                overwrite_list.push(entry);
             }
-            self.term.progress_inc();
         }
-        self.term.progress_finish();
 
         self.term.new_progress(dest_files.len(), "Checking")?;
         for entry in dest_files {
@@ -345,7 +493,104 @@ impl Task {
         }
         self.term.progress_finish();
 
-        Ok((add_list, overwrite_list, remove_list))
+        let RenameDetection {
+            add_list,
+            remove_list,
+            rename_list,
+        } = self.detect_renames(src, dest, add_list, remove_list, hash_algo)?;
+
+        Ok(ToDoList {
+            add_list,
+            overwrite_list,
+            remove_list,
+            rename_list,
+        })
+    }
+
+    /// Finds `add_list`/`remove_list` pairs that are really the same file moved or renamed in
+    /// the source, so the caller can replace a full copy+delete with a single in-place
+    /// `fs::rename`. Candidates are grouped by size first to keep the number of hashes small.
+    fn detect_renames(
+        &mut self,
+        src: &Path,
+        dest: &Path,
+        add_list: Vec<PathBuf>,
+        remove_list: Vec<PathBuf>,
+        hash_algo: HashAlgo,
+    ) -> Result<RenameDetection> {
+        let mut remove_by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        for entry in &remove_list {
+            let path = dest.join(entry);
+            // Removed directories land in `remove_list` alongside files, but renames only make
+            // sense between files: a directory's size is not meaningfully comparable to a
+            // file's, and hashing a directory below would fail outright.
+            if path.is_dir() {
+                continue;
+            }
+            let size = path
+                .metadata()
+                .with_context(|| format!("Failed to get metadata: {}", path.display()))?
+                .len();
+            remove_by_size.entry(size).or_default().push(entry.clone());
+        }
+
+        let mut renamed_adds = HashSet::new();
+        let mut renamed_removes = HashSet::new();
+        let mut rename_list = Vec::new();
+
+        if !remove_by_size.is_empty() {
+            self.term.act("Detecting", "renamed files")?;
+
+            for entry in &add_list {
+                let add_path = src.join(entry);
+                let size = add_path
+                    .metadata()
+                    .with_context(|| format!("Failed to get metadata: {}", add_path.display()))?
+                    .len();
+
+                let Some(candidates) = remove_by_size.get(&size) else {
+                    continue;
+                };
+
+                // Hash the add-side file once per entry rather than once per same-size
+                // candidate. Each same-size remove-side candidate is still hashed afresh for
+                // every add entry it is compared against.
+                let add_hash = hash_file(&add_path, hash_algo)?;
+
+                for candidate in candidates {
+                    if renamed_removes.contains(candidate) {
+                        continue;
+                    }
+
+                    let remove_path = dest.join(candidate);
+                    if add_hash != hash_file(&remove_path, hash_algo)? {
+                        continue;
+                    }
+
+                    log::debug!("> {} -> {}", candidate.display(), entry.display());
+
+                    rename_list.push((remove_path, dest.join(entry)));
+                    renamed_adds.insert(entry.clone());
+                    renamed_removes.insert(candidate.clone());
+                    break;
+                }
+            }
+        }
+
+        let add_list = add_list
+            .into_iter()
+            .filter(|e| !renamed_adds.contains(e))
+            .collect();
+        let remove_list = remove_list
+            .into_iter()
+            .filter(|e| !renamed_removes.contains(e))
+            .collect();
+
+        Ok(RenameDetection {
+            add_list,
+            remove_list,
+            rename_list,
+        })
     }
 
     fn execute_list(
@@ -353,19 +598,61 @@ impl Task {
         name: &str,
         action: impl Into<Cow<'static, str>>,
         list: &[PathBuf],
-        f: fn(&Path, &Path, &Path) -> Result<()>,
+        f: fn(&Path, &Path, &Path, BackupMode, &str, bool, bool) -> Result<()>,
         src: &Path,
         dest: &Path,
     ) -> Result<()> {
         if !list.is_empty() {
             self.term.act("Processing", &format!("{name} list"))?;
             self.term.new_progress(list.len(), action)?;
-            for entry in list.iter() {
-                self.term.progress_msg(entry.display().to_string());
-                if let Err(e) = (f)(src, dest, entry) {
+
+            let progress = self.term.progress_handle();
+            let src = src.to_path_buf();
+            let dest = dest.to_path_buf();
+            let name = name.to_owned();
+            let backup = self.backup;
+            let suffix = self.suffix.clone();
+            let trash = self.trash;
+            let preserve = self.preserve;
+            let results = run_in_parallel(self.jobs, list.to_vec(), progress, move |entry| {
+                (f)(&src, &dest, &entry, backup, &suffix, trash, preserve)
+            });
+
+            for result in results {
+                if let Err(e) = result {
                     log::warn!("Failed to execute {name} task:\n{e:?}");
-                };
+                }
             }
+
+            self.term.progress_finish();
+        }
+
+        Ok(())
+    }
+
+    fn execute_rename_list(&mut self, list: &[(PathBuf, PathBuf)]) -> Result<()> {
+        if !list.is_empty() {
+            self.term.act("Processing", "rename list")?;
+            self.term.new_progress(list.len(), "Renaming")?;
+
+            for (from, to) in list {
+                self.term.progress_msg(to.display().to_string());
+                let result = (|| -> Result<()> {
+                    if let Some(parent) = to.parent() {
+                        fs::create_dir_all(parent).with_context(|| {
+                            format!("Failed to create directory: {}", parent.display())
+                        })?;
+                    }
+                    fs::rename(from, to).with_context(|| {
+                        format!("Failed to rename: {} -> {}", from.display(), to.display())
+                    })
+                })();
+                if let Err(e) = result {
+                    log::warn!("Failed to execute rename task:\n{e:?}");
+                }
+                self.term.progress_inc();
+            }
+
             self.term.progress_finish();
         }
 
@@ -373,6 +660,56 @@ impl Task {
     }
 }
 
+/// Runs `f` over `items` using up to `jobs` worker threads pulled from a shared queue,
+/// incrementing `progress` once per completed item. Results are returned in the same
+/// order as `items`.
+fn run_in_parallel<T, R, F>(
+    jobs: usize,
+    items: Vec<T>,
+    progress: Option<ProgressBar>,
+    f: F,
+) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let queue = Arc::new(Mutex::new(items.into_iter().enumerate()));
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let f = Arc::new(f);
+
+    let workers: Vec<_> = (0..jobs.max(1))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let results = Arc::clone(&results);
+            let progress = progress.clone();
+            let f = Arc::clone(&f);
+            thread::spawn(move || loop {
+                let next = queue.lock().unwrap().next();
+                let Some((index, item)) = next else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap().push((index, result));
+                if let Some(progress) = &progress {
+                    progress.inc(1);
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().expect("worker thread panicked");
+    }
+
+    let mut results = Arc::into_inner(results)
+        .expect("all worker threads have finished, so no other Arc clone can remain")
+        .into_inner()
+        .unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
 fn generate_globset(gs: &[Glob]) -> Result<GlobSet> {
     let mut builder = GlobSetBuilder::new();
     for g in gs {
@@ -391,7 +728,13 @@ struct Newest {
     created: SystemTime,
 }
 
-fn need_overwrite(src: &Path, dest: &Path, mode: OverwriteMode) -> Result<bool> {
+fn need_overwrite(
+    src: &Path,
+    dest: &Path,
+    mode: OverwriteMode,
+    scan_start: SystemTime,
+    hash_algo: HashAlgo,
+) -> Result<bool> {
     if mode == OverwriteMode::Always {
         return Ok(false);
     }
@@ -402,15 +745,13 @@ fn need_overwrite(src: &Path, dest: &Path, mode: OverwriteMode) -> Result<bool>
     let src_disp = src.display();
     let dest_disp = dest.display();
 
-    let src_len = src
+    let src_metadata = src
         .metadata()
-        .with_context(|| format!("Failed to get metadata of source file: {src_disp}",))?
-        .len();
-    let dest_len = dest
+        .with_context(|| format!("Failed to get metadata of source file: {src_disp}",))?;
+    let dest_metadata = dest
         .metadata()
-        .with_context(|| format!("Failed to get metadata of destination file: {dest_disp}",))?
-        .len();
-    if src_len != dest_len {
+        .with_context(|| format!("Failed to get metadata of destination file: {dest_disp}",))?;
+    if src_metadata.len() != dest_metadata.len() {
         return Ok(false);
     }
 
@@ -418,46 +759,555 @@ fn need_overwrite(src: &Path, dest: &Path, mode: OverwriteMode) -> Result<bool>
         return Ok(true);
     }
 
-    let mut src_file =
-        File::open(src).with_context(|| format!("Failed to open source file: {src_disp}"))?;
-    let mut src_hasher = Sha1::new();
-    io::copy(&mut src_file, &mut src_hasher)
-        .with_context(|| format!("Failed to copy source file to hasher: {src_disp}"))?;
-    let src_hash = src_hasher.finalize();
-
-    let mut dest_file = File::open(dest)
-        .with_context(|| format!("Failed to open destination file: {dest_disp}"))?;
-    let mut dest_hasher = Sha1::new();
-    io::copy(&mut dest_file, &mut dest_hasher)
-        .with_context(|| format!("Failed to copy destination file to hasher: {dest_disp}"))?;
-    let dest_hash = dest_hasher.finalize();
+    if mode == OverwriteMode::Mtime {
+        let src_modified = src_metadata
+            .modified()
+            .with_context(|| format!("Failed to get mtime of source file: {src_disp}"))?;
+
+        if !is_ambiguous_mtime(src_modified, scan_start) {
+            let dest_modified = dest_metadata
+                .modified()
+                .with_context(|| format!("Failed to get mtime of destination file: {dest_disp}"))?;
+            return Ok(truncate_to_secs(src_modified) <= truncate_to_secs(dest_modified));
+        }
+
+        // The source's mtime falls within the same second as the start of this run, so it
+        // could be rewritten again within that second without its mtime advancing. Fall back
+        // to a content comparison rather than risk silently missing the edit.
+    }
+
+    let src_hash =
+        hash_file(src, hash_algo).with_context(|| format!("Failed to hash: {src_disp}"))?;
+    let dest_hash =
+        hash_file(dest, hash_algo).with_context(|| format!("Failed to hash: {dest_disp}"))?;
 
     Ok(src_hash == dest_hash)
 }
 
-fn remove(_: &Path, dest: &Path, entry: &Path) -> Result<()> {
+fn hash_file(path: &Path, algo: HashAlgo) -> Result<Hash> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+
+    Ok(match algo {
+        HashAlgo::Sha1 => {
+            let mut hasher = Sha1::new();
+            io::copy(&mut file, &mut hasher)
+                .with_context(|| format!("Failed to copy file to hasher: {}", path.display()))?;
+            Hash::Sha1(hasher.finalize().to_vec())
+        }
+        HashAlgo::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            io::copy(&mut file, &mut hasher)
+                .with_context(|| format!("Failed to copy file to hasher: {}", path.display()))?;
+            Hash::Blake3(hasher.finalize())
+        }
+    })
+}
+
+/// Truncates a `SystemTime` down to whole seconds, matching the coarsest mtime granularity a
+/// filesystem might offer, so that comparisons aren't thrown off by sub-second precision.
+fn truncate_to_secs(time: SystemTime) -> SystemTime {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(std::time::Duration::ZERO, |d| {
+            std::time::Duration::from_secs(d.as_secs())
+        });
+    SystemTime::UNIX_EPOCH + secs
+}
+
+/// A source mtime in the same second as `scan_start` is ambiguous: the file could be rewritten
+/// again before this run finishes without its mtime advancing, so a plain comparison can't be
+/// trusted.
+fn is_ambiguous_mtime(mtime: SystemTime, scan_start: SystemTime) -> bool {
+    truncate_to_secs(mtime) == truncate_to_secs(scan_start)
+}
+
+fn remove(
+    _: &Path,
+    dest: &Path,
+    entry: &Path,
+    backup: BackupMode,
+    suffix: &str,
+    trash: bool,
+    _preserve: bool,
+) -> Result<()> {
     let path = dest.join(entry);
 
-    if path.is_dir() {
-        fs::remove_dir(&path)
+    let backed_up = backup_existing(&path, backup, suffix)
+        .with_context(|| format!("Failed to back up: {}", path.display()))?;
+    if backed_up {
+        return Ok(());
+    }
+
+    if trash {
+        trash::delete(&path)
+            .with_context(|| format!("Failed to move to trash: {}", path.display()))?;
+    } else if path.is_dir() {
+        fs::remove_dir(&path).with_context(|| format!("Failed to remove: {}", path.display()))?;
     } else {
-        fs::remove_file(&path)
+        fs::remove_file(&path).with_context(|| format!("Failed to remove: {}", path.display()))?;
     }
-    .with_context(|| format!("Failed to remove: {}", path.display()))?;
 
     Ok(())
 }
 
-fn copy(src: &Path, dest: &Path, entry: &Path) -> Result<()> {
+fn copy(
+    src: &Path,
+    dest: &Path,
+    entry: &Path,
+    backup: BackupMode,
+    suffix: &str,
+    _trash: bool,
+    preserve: bool,
+) -> Result<()> {
     let src = src.join(entry);
     let dest = dest.join(entry);
 
-    if let Some(parent) = dest.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("Failed to create directory error: {}", parent.display(),))?;
+    let parent = dest
+        .parent()
+        .context("Destination path has no parent directory")?;
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory error: {}", parent.display()))?;
+
+    backup_existing(&dest, backup, suffix)
+        .with_context(|| format!("Failed to back up: {}", dest.display()))?;
+    // `copy` always (re)writes `dest` afterwards, so whether a backup actually happened doesn't
+    // change what it does next — unlike `remove`, which must stop once the path is gone.
+
+    let mut src_file =
+        File::open(&src).with_context(|| format!("Failed to open source file: {}", src.display()))?;
+    let src_metadata = src_file
+        .metadata()
+        .with_context(|| format!("Failed to get metadata of source file: {}", src.display()))?;
+
+    let tmp_path = temp_path_in(parent, &dest);
+    let mut open_options = OpenOptions::new();
+    open_options.write(true).create_new(true);
+    #[cfg(unix)]
+    open_options.mode(src_metadata.mode());
+    let mut tmp_file = open_options
+        .open(&tmp_path)
+        .with_context(|| format!("Failed to create temporary file: {}", tmp_path.display()))?;
+
+    // `open(2)`'s mode is masked by the process umask, unlike `fs::copy`, which always chmods
+    // the destination to match the source exactly. Set it explicitly so a plain copy (without
+    // `--preserve`) still matches the source's permission bits regardless of umask.
+    #[cfg(unix)]
+    fs::set_permissions(&tmp_path, src_metadata.permissions()).with_context(|| {
+        format!(
+            "Failed to set permissions on temporary file: {}",
+            tmp_path.display()
+        )
+    })?;
+
+    io::copy(&mut src_file, &mut tmp_file).with_context(|| {
+        format!(
+            "Failed to copy: {} -> {}",
+            src.display(),
+            tmp_path.display()
+        )
+    })?;
+    tmp_file
+        .sync_all()
+        .with_context(|| format!("Failed to sync temporary file: {}", tmp_path.display()))?;
+    drop(tmp_file);
+
+    fs::rename(&tmp_path, &dest).with_context(|| {
+        format!(
+            "Failed to rename temporary file into place: {} -> {}",
+            tmp_path.display(),
+            dest.display()
+        )
+    })?;
+
+    if preserve {
+        preserve_metadata(&dest, &src_metadata)
+            .with_context(|| format!("Failed to preserve metadata: {}", dest.display()))?;
     }
 
-    fs::copy(&src, dest).with_context(|| format!("Failed to copy: {}", src.display()))?;
+    Ok(())
+}
+
+/// Returns a path for a sibling temporary file that `copy` writes into before renaming it
+/// over `dest`, so an interrupted copy never leaves a truncated file at the final path.
+fn temp_path_in(parent: &Path, dest: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let unique = COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+    parent.join(format!(
+        ".chroni-tmp-{file_name}-{}-{unique:x}",
+        std::process::id()
+    ))
+}
+
+fn preserve_metadata(dest: &Path, src_metadata: &fs::Metadata) -> Result<()> {
+    let mtime = FileTime::from_last_modification_time(src_metadata);
+    filetime::set_file_mtime(dest, mtime)
+        .with_context(|| format!("Failed to preserve mtime: {}", dest.display()))?;
+
+    #[cfg(unix)]
+    {
+        // Change ownership before permissions: on most systems a non-root-triggered chown
+        // clears setuid/setgid bits, so doing it first lets the following chmod make them
+        // stick. Unprivileged processes can't chown at all (not even to their own uid/gid),
+        // so treat EPERM as best-effort rather than a hard failure, matching `cp --preserve`.
+        match std::os::unix::fs::chown(dest, Some(src_metadata.uid()), Some(src_metadata.gid())) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                log::warn!("Failed to preserve ownership of {}: {e}", dest.display());
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to preserve ownership: {}", dest.display()))
+            }
+        }
+
+        fs::set_permissions(dest, src_metadata.permissions())
+            .with_context(|| format!("Failed to preserve permissions: {}", dest.display()))?;
+    }
 
     Ok(())
 }
+
+/// Renames an existing destination file out of the way according to `mode` before it is
+/// overwritten or removed. A no-op when `mode` is `BackupMode::None` or the path doesn't exist.
+/// Renames `path` out of the way if backups are enabled and it exists, returning `true` if it
+/// did so. Callers that are about to remove `path` outright must check this: once the file has
+/// been moved to its backup location, there's nothing left at `path` to delete.
+fn backup_existing(path: &Path, mode: BackupMode, suffix: &str) -> Result<bool> {
+    if mode == BackupMode::None || !path.exists() {
+        return Ok(false);
+    }
+
+    let backup_path = match mode {
+        BackupMode::None => unreachable!(),
+        BackupMode::Simple => simple_backup_path(path, suffix),
+        BackupMode::Numbered => numbered_backup_path(path),
+    };
+
+    fs::rename(path, &backup_path).with_context(|| {
+        format!(
+            "Failed to rename to backup: {} -> {}",
+            path.display(),
+            backup_path.display()
+        )
+    })?;
+
+    Ok(true)
+}
+
+fn simple_backup_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn numbered_backup_path(path: &Path) -> PathBuf {
+    let mut n = 1;
+    loop {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".~{n}~"));
+        let candidate = PathBuf::from(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("chroni-test-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn test_task(overwrite_mode: OverwriteMode) -> Task {
+        Task {
+            src: PathBuf::new(),
+            dest: PathBuf::new(),
+            overwrite_mode,
+            only_newest: Vec::new(),
+            dry_run: false,
+            jobs: 1,
+            backup: BackupMode::None,
+            suffix: "~".to_owned(),
+            trash: false,
+            preserve: false,
+            hash_algo: HashAlgo::Sha1,
+            term: Term::default(),
+        }
+    }
+
+    #[test]
+    fn generate_to_do_list_overwrites_changed_files_in_every_mode() {
+        for mode in [
+            OverwriteMode::Always,
+            OverwriteMode::FastComp,
+            OverwriteMode::DeepComp,
+            OverwriteMode::Mtime,
+        ] {
+            let tmp = TempDir::new(&format!("{mode:?}"));
+            let src_dir = tmp.0.join("src");
+            let dest_dir = tmp.0.join("dest");
+            fs::create_dir_all(&src_dir).unwrap();
+            fs::create_dir_all(&dest_dir).unwrap();
+
+            let entry = PathBuf::from("file.txt");
+            fs::write(src_dir.join(&entry), b"new content").unwrap();
+            fs::write(dest_dir.join(&entry), b"old content, different length").unwrap();
+
+            // Give the source a fresh mtime that won't be treated as ambiguous under
+            // `OverwriteMode::Mtime`.
+            let scan_start = SystemTime::now() - Duration::from_secs(5);
+            filetime::set_file_mtime(src_dir.join(&entry), FileTime::from_system_time(SystemTime::now()))
+                .unwrap();
+
+            let mut task = test_task(mode);
+            let options = RunOptions {
+                overwrite_mode: mode,
+                scan_start,
+                hash_algo: HashAlgo::Sha1,
+            };
+            let to_do_list = task
+                .generate_to_do_list(
+                    &src_dir,
+                    &dest_dir,
+                    std::slice::from_ref(&entry),
+                    std::slice::from_ref(&entry),
+                    options,
+                )
+                .unwrap();
+
+            assert!(to_do_list.add_list.is_empty());
+            assert!(to_do_list.remove_list.is_empty());
+            assert!(to_do_list.rename_list.is_empty());
+            assert_eq!(
+                to_do_list.overwrite_list,
+                vec![entry],
+                "{mode:?} should overwrite a file whose content changed"
+            );
+        }
+    }
+
+    #[test]
+    fn generate_to_do_list_never_mode_skips_changed_files() {
+        let tmp = TempDir::new("never");
+        let src_dir = tmp.0.join("src");
+        let dest_dir = tmp.0.join("dest");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(&dest_dir).unwrap();
+
+        let entry = PathBuf::from("file.txt");
+        fs::write(src_dir.join(&entry), b"new content").unwrap();
+        fs::write(dest_dir.join(&entry), b"old content, different length").unwrap();
+
+        let mut task = test_task(OverwriteMode::Never);
+        let options = RunOptions {
+            overwrite_mode: OverwriteMode::Never,
+            scan_start: SystemTime::now(),
+            hash_algo: HashAlgo::Sha1,
+        };
+        let include_files = [entry.clone()];
+        let dest_files = [entry];
+        let to_do_list = task
+            .generate_to_do_list(&src_dir, &dest_dir, &include_files, &dest_files, options)
+            .unwrap();
+
+        assert!(to_do_list.overwrite_list.is_empty());
+    }
+
+    #[test]
+    fn remove_with_backup_mode_renames_instead_of_deleting() {
+        let tmp = TempDir::new("remove-backup");
+        let dest_dir = tmp.0.join("dest");
+        fs::create_dir_all(&dest_dir).unwrap();
+
+        let entry = PathBuf::from("file.txt");
+        fs::write(dest_dir.join(&entry), b"keep me").unwrap();
+
+        remove(
+            Path::new(""),
+            &dest_dir,
+            &entry,
+            BackupMode::Simple,
+            "~",
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert!(!dest_dir.join(&entry).exists());
+        assert_eq!(
+            fs::read(dest_dir.join("file.txt~")).unwrap(),
+            b"keep me",
+            "the original content should have been preserved under the backup name"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_without_preserve_still_matches_source_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new("copy-permissions");
+        let src_dir = tmp.0.join("src");
+        let dest_dir = tmp.0.join("dest");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(&dest_dir).unwrap();
+
+        let entry = PathBuf::from("file.txt");
+        fs::write(src_dir.join(&entry), b"content").unwrap();
+        fs::set_permissions(src_dir.join(&entry), fs::Permissions::from_mode(0o741)).unwrap();
+
+        copy(&src_dir, &dest_dir, &entry, BackupMode::None, "~", false, false).unwrap();
+
+        assert_eq!(fs::read(dest_dir.join(&entry)).unwrap(), b"content");
+        let dest_mode = dest_dir.join(&entry).metadata().unwrap().permissions().mode();
+        assert_eq!(
+            dest_mode & 0o777,
+            0o741,
+            "a plain copy (no --preserve) should still carry over the source's exact \
+             permission bits, not whatever `open(2)` leaves after the umask"
+        );
+    }
+
+    #[test]
+    fn copy_with_preserve_restores_source_mtime() {
+        let tmp = TempDir::new("copy-preserve-mtime");
+        let src_dir = tmp.0.join("src");
+        let dest_dir = tmp.0.join("dest");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(&dest_dir).unwrap();
+
+        let entry = PathBuf::from("file.txt");
+        fs::write(src_dir.join(&entry), b"content").unwrap();
+        let src_mtime = SystemTime::now() - Duration::from_secs(3600);
+        filetime::set_file_mtime(src_dir.join(&entry), FileTime::from_system_time(src_mtime))
+            .unwrap();
+
+        copy(&src_dir, &dest_dir, &entry, BackupMode::None, "~", false, true).unwrap();
+
+        let dest_mtime = dest_dir.join(&entry).metadata().unwrap().modified().unwrap();
+        assert_eq!(
+            truncate_to_secs(dest_mtime),
+            truncate_to_secs(src_mtime),
+            "--preserve should carry the source's mtime over to the destination"
+        );
+    }
+
+    #[test]
+    fn detect_renames_turns_moved_file_into_rename_entry() {
+        let tmp = TempDir::new("detect-renames");
+        let src_dir = tmp.0.join("src");
+        let dest_dir = tmp.0.join("dest");
+        fs::create_dir_all(src_dir.join("new")).unwrap();
+        fs::create_dir_all(dest_dir.join("old")).unwrap();
+
+        fs::write(src_dir.join("new").join("file.txt"), b"same content").unwrap();
+        fs::write(dest_dir.join("old").join("file.txt"), b"same content").unwrap();
+
+        let add_entry = PathBuf::from("new/file.txt");
+        let remove_entry = PathBuf::from("old/file.txt");
+
+        let mut task = test_task(OverwriteMode::FastComp);
+        let detection = task
+            .detect_renames(
+                &src_dir,
+                &dest_dir,
+                vec![add_entry.clone()],
+                vec![remove_entry.clone()],
+                HashAlgo::Sha1,
+            )
+            .unwrap();
+
+        assert!(detection.add_list.is_empty());
+        assert!(detection.remove_list.is_empty());
+        assert_eq!(
+            detection.rename_list,
+            vec![(
+                dest_dir.join(&remove_entry),
+                dest_dir.join(&add_entry)
+            )]
+        );
+
+        task.execute_rename_list(&detection.rename_list).unwrap();
+
+        assert!(!dest_dir.join(&remove_entry).exists());
+        assert_eq!(
+            fs::read(dest_dir.join(&add_entry)).unwrap(),
+            b"same content",
+            "the file should have been renamed in place into its (new) subdirectory"
+        );
+    }
+
+    #[test]
+    fn need_overwrite_mtime_mode_falls_back_to_hash_on_ambiguous_mtime() {
+        let tmp = TempDir::new("need-overwrite-ambiguous-mtime");
+        let src_dir = tmp.0.join("src");
+        let dest_dir = tmp.0.join("dest");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(&dest_dir).unwrap();
+
+        // A `scan_start` matching the files' mtime down to the second is exactly the
+        // "ambiguous" case `is_ambiguous_mtime` is meant to catch.
+        let scan_start = SystemTime::now();
+
+        let changed = PathBuf::from("changed.txt");
+        fs::write(src_dir.join(&changed), b"aaaa").unwrap();
+        fs::write(dest_dir.join(&changed), b"bbbb").unwrap();
+
+        let unchanged = PathBuf::from("unchanged.txt");
+        fs::write(src_dir.join(&unchanged), b"same").unwrap();
+        fs::write(dest_dir.join(&unchanged), b"same").unwrap();
+
+        for entry in [&changed, &unchanged] {
+            filetime::set_file_mtime(src_dir.join(entry), FileTime::from_system_time(scan_start))
+                .unwrap();
+        }
+
+        assert!(
+            !need_overwrite(
+                &src_dir.join(&changed),
+                &dest_dir.join(&changed),
+                OverwriteMode::Mtime,
+                scan_start,
+                HashAlgo::Sha1,
+            )
+            .unwrap(),
+            "same-second mtimes with different content should still be detected via hashing"
+        );
+        assert!(
+            need_overwrite(
+                &src_dir.join(&unchanged),
+                &dest_dir.join(&unchanged),
+                OverwriteMode::Mtime,
+                scan_start,
+                HashAlgo::Sha1,
+            )
+            .unwrap(),
+            "same-second mtimes with identical content should be left alone"
+        );
+    }
+}